@@ -1,28 +1,82 @@
 use std::error::Error;
 use std::fmt::Write;
+use std::io;
 use std::process;
 
 use clap::{crate_authors, crate_version, App, Arg};
 use csv::{ReaderBuilder, StringRecord, Trim};
+use rusqlite::{types::ValueRef, Connection};
+use unicode_width::UnicodeWidthStr;
 
 fn main() {
     let matches = App::new("rtab")
         .version(crate_version!())
         .author(crate_authors!())
         .about("Generate tables from CSV.")
-        .arg(Arg::with_name("FILE").required(true))
+        .arg(
+            Arg::with_name("FILE")
+                .help("Input CSV file; - or omitted reads from stdin")
+                .default_value("-"),
+        )
         .arg(
             Arg::with_name("STYLE")
                 .long("style")
                 .help("Sets table style")
                 .takes_value(true)
-                .possible_values(&["basic"]),
+                .possible_values(&["basic", "grid", "rounded", "markdown"]),
+        )
+        .arg(
+            Arg::with_name("HEADER")
+                .long("header")
+                .help("Treats the first record as a header row"),
+        )
+        .arg(
+            Arg::with_name("DELIMITER")
+                .short("d")
+                .long("delimiter")
+                .help("Sets the field delimiter")
+                .takes_value(true)
+                .default_value(","),
+        )
+        .arg(
+            Arg::with_name("ALIGN")
+                .long("align")
+                .help("Sets per-column alignment, e.g. \"lrc\" or \"1:r,3:c\"")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("TEMPLATE")
+                .long("template")
+                .help("Renders each record through a template instead of a table")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("SQL")
+                .long("sql")
+                .help("Runs a SQL query over the records and renders the result")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("TSV")
+                .long("tsv")
+                .help("Emits --sql results as tab-separated values instead of a table"),
         )
         .get_matches();
 
     // Open input file for reading.
     let path = matches.value_of("FILE").unwrap();
-    let records = match parse_records(path) {
+    let delimiter_str = matches.value_of("DELIMITER").unwrap();
+    let delimiter = match delimiter_str.as_bytes() {
+        [byte] => *byte,
+        _ => {
+            eprintln!(
+                "Error: --delimiter must be exactly one byte, got {:?}",
+                delimiter_str
+            );
+            process::exit(1);
+        }
+    };
+    let records = match parse_records(path, delimiter) {
         Ok(records) => records,
         Err(e) => {
             eprintln!("Error parsing file: {}", e);
@@ -30,11 +84,77 @@ fn main() {
         }
     };
 
-    // Generate formatted table.
+    let header = matches.is_present("HEADER");
+
+    // Render through a template instead of a table, if requested.
+    if let Some(template) = matches.value_of("TEMPLATE") {
+        let header_row = if header { records.first() } else { None };
+        let rows = if header && !records.is_empty() {
+            &records[1..]
+        } else {
+            &records[..]
+        };
+
+        let mut output = String::new();
+        for record in rows {
+            output.push_str(&render_template(template, record, header_row));
+            output.push('\n');
+        }
+
+        print!("{}", output);
+        return;
+    }
+
     let style = matches.value_of("STYLE").unwrap_or("basic");
+
+    // Run the records through an in-memory SQLite table instead, if requested.
+    if let Some(sql) = matches.value_of("SQL") {
+        let (columns, rows) = match run_query(&records, header, sql) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Error running query: {}", e);
+                process::exit(1);
+            }
+        };
+
+        if matches.is_present("TSV") {
+            print!("{}", tsv_table(&columns, &rows));
+            return;
+        }
+
+        let query_records: Vec<StringRecord> = std::iter::once(StringRecord::from(columns))
+            .chain(rows.into_iter().map(StringRecord::from))
+            .collect();
+        let widths = calculate_widths(&query_records);
+        let alignments =
+            resolve_alignments(&query_records, matches.value_of("ALIGN"), widths.len(), true);
+        let output = match style {
+            "basic" => basic_table(&query_records, &widths, &alignments, true),
+            "grid" => grid_table(&query_records, &widths, &alignments, false, true),
+            "rounded" => grid_table(&query_records, &widths, &alignments, true, true),
+            "markdown" => markdown_table(&query_records, &widths, &alignments),
+            _ => unreachable!(),
+        };
+
+        match output {
+            Ok(output) => print!("{}", output),
+            Err(e) => {
+                eprintln!("Error formatting output: {}", e);
+                process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    // Generate formatted table.
     let widths = calculate_widths(&records);
+    let alignments = resolve_alignments(&records, matches.value_of("ALIGN"), widths.len(), header);
     let output = match style {
-        "basic" => basic_table(&records, &widths),
+        "basic" => basic_table(&records, &widths, &alignments, header),
+        "grid" => grid_table(&records, &widths, &alignments, false, header),
+        "rounded" => grid_table(&records, &widths, &alignments, true, header),
+        "markdown" => markdown_table(&records, &widths, &alignments),
         _ => unreachable!(),
     };
 
@@ -49,12 +169,17 @@ fn main() {
 }
 
 /// Generate a basic table.
-fn basic_table(records: &[StringRecord], widths: &[usize]) -> Result<String, Box<dyn Error>> {
+fn basic_table(
+    records: &[StringRecord],
+    widths: &[usize],
+    alignments: &[Alignment],
+    header: bool,
+) -> Result<String, Box<dyn Error>> {
     // Build output string.
     let mut output = String::new();
-    for record in records {
-        for (i, field) in record.iter().enumerate() {
-            write!(output, "{:width$}", field, width = widths[i] + 1)?;
+    for (i, record) in records.iter().enumerate() {
+        for (j, field) in record.iter().enumerate() {
+            write!(output, "{} ", align_field(field, widths[j], alignments[j]))?;
         }
 
         // Trim trailing whitespace.
@@ -62,29 +187,404 @@ fn basic_table(records: &[StringRecord], widths: &[usize]) -> Result<String, Box
         output.truncate(len);
 
         writeln!(output)?;
+
+        if header && i == 0 {
+            writeln!(output, "{}", header_rule(widths))?;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Build the separator rule printed below a header row.
+fn header_rule(widths: &[usize]) -> String {
+    widths
+        .iter()
+        .map(|w| "-".repeat(*w))
+        .collect::<Vec<_>>()
+        .join("-+-")
+}
+
+/// Generate a table bordered with box-drawing characters.
+fn grid_table(
+    records: &[StringRecord],
+    widths: &[usize],
+    alignments: &[Alignment],
+    rounded: bool,
+    header: bool,
+) -> Result<String, Box<dyn Error>> {
+    let (top_left, top_right, bottom_left, bottom_right) = if rounded {
+        ('╭', '╮', '╰', '╯')
+    } else {
+        ('┌', '┐', '└', '┘')
+    };
+
+    let mut output = String::new();
+    writeln!(
+        output,
+        "{}",
+        rule_line(widths, top_left, '┬', top_right, '─')
+    )?;
+    for (i, record) in records.iter().enumerate() {
+        write!(output, "│")?;
+        for (j, field) in record.iter().enumerate() {
+            write!(output, " {} │", align_field(field, widths[j], alignments[j]))?;
+        }
+        writeln!(output)?;
+
+        if header && i == 0 && i < records.len() - 1 {
+            writeln!(output, "{}", rule_line(widths, '╞', '╪', '╡', '═'))?;
+        } else if i < records.len() - 1 {
+            writeln!(output, "{}", rule_line(widths, '├', '┼', '┤', '─'))?;
+        }
+    }
+    writeln!(
+        output,
+        "{}",
+        rule_line(widths, bottom_left, '┴', bottom_right, '─')
+    )?;
+
+    Ok(output)
+}
+
+/// Generate a Markdown-style pipe table.
+fn markdown_table(
+    records: &[StringRecord],
+    widths: &[usize],
+    alignments: &[Alignment],
+) -> Result<String, Box<dyn Error>> {
+    let mut output = String::new();
+    for (i, record) in records.iter().enumerate() {
+        write!(output, "|")?;
+        for (j, field) in record.iter().enumerate() {
+            write!(output, " {} |", align_field(field, widths[j], alignments[j]))?;
+        }
+        writeln!(output)?;
+
+        if i == 0 {
+            write!(output, "|")?;
+            for width in widths {
+                write!(output, " {} |", "-".repeat((*width).max(3)))?;
+            }
+            writeln!(output)?;
+        }
     }
 
     Ok(output)
 }
 
+/// Build a horizontal rule line for the given column widths.
+fn rule_line(widths: &[usize], left: char, mid: char, right: char, fill: char) -> String {
+    let mut line = String::new();
+    line.push(left);
+    for (i, width) in widths.iter().enumerate() {
+        line.push_str(&fill.to_string().repeat(width + 2));
+        line.push(if i < widths.len() - 1 { mid } else { right });
+    }
+
+    line
+}
+
+/// Horizontal alignment of a column's contents.
+#[derive(Clone, Copy)]
+enum Alignment {
+    Left,
+    Right,
+    Center,
+}
+
+/// Pad a field to the given display width according to its alignment.
+fn align_field(field: &str, width: usize, alignment: Alignment) -> String {
+    let padding = width.saturating_sub(field.width());
+    match alignment {
+        Alignment::Left => format!("{}{}", field, " ".repeat(padding)),
+        Alignment::Right => format!("{}{}", " ".repeat(padding), field),
+        Alignment::Center => {
+            let left = padding / 2;
+            let right = padding - left;
+            format!("{}{}{}", " ".repeat(left), field, " ".repeat(right))
+        }
+    }
+}
+
+/// Resolve the alignment to use for each column, combining any explicit
+/// `--align` spec with automatic right-alignment of all-numeric columns.
+fn resolve_alignments(
+    records: &[StringRecord],
+    spec: Option<&str>,
+    columns: usize,
+    header: bool,
+) -> Vec<Alignment> {
+    let mut explicit: Vec<Option<Alignment>> = vec![None; columns];
+
+    if let Some(spec) = spec {
+        if spec.contains(':') {
+            for entry in spec.split(',') {
+                let mut parts = entry.splitn(2, ':');
+                let index = parts.next().and_then(|s| s.trim().parse::<usize>().ok());
+                let align = parts.next().and_then(|s| parse_alignment(s.trim()));
+                match (index, align) {
+                    (Some(index), Some(align)) if (1..=columns).contains(&index) => {
+                        explicit[index - 1] = Some(align);
+                    }
+                    _ => {}
+                }
+            }
+        } else {
+            for (i, c) in spec.chars().enumerate().take(columns) {
+                explicit[i] = parse_alignment(&c.to_string());
+            }
+        }
+    }
+
+    explicit
+        .into_iter()
+        .enumerate()
+        .map(|(i, align)| align.unwrap_or_else(|| auto_alignment(records, i, header)))
+        .collect()
+}
+
+/// Parse a single alignment specifier character ('l', 'r', or 'c').
+fn parse_alignment(s: &str) -> Option<Alignment> {
+    match s {
+        "l" | "L" => Some(Alignment::Left),
+        "r" | "R" => Some(Alignment::Right),
+        "c" | "C" => Some(Alignment::Center),
+        _ => None,
+    }
+}
+
+/// Right-align a column if every non-empty field in it parses as a number.
+/// The header row, if any, is ignored so a non-numeric column name doesn't
+/// defeat detection.
+fn auto_alignment(records: &[StringRecord], column: usize, header: bool) -> Alignment {
+    let data = if header && !records.is_empty() {
+        &records[1..]
+    } else {
+        records
+    };
+
+    let mut fields = data
+        .iter()
+        .filter_map(|r| r.get(column))
+        .filter(|f| !f.is_empty())
+        .peekable();
+
+    if fields.peek().is_some() && fields.clone().all(|f| f.parse::<f64>().is_ok()) {
+        Alignment::Right
+    } else {
+        Alignment::Left
+    }
+}
+
+/// Render a template for a single record, substituting `{0}`, `{1}`, ... by
+/// column index and, when header names are available, `{Name}` by header.
+/// `{{` and `}}` are escaped to literal braces.
+fn render_template(template: &str, record: &StringRecord, headers: Option<&StringRecord>) -> String {
+    let mut output = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+            '{' => {
+                let mut token = String::new();
+                for tc in chars.by_ref() {
+                    if tc == '}' {
+                        break;
+                    }
+                    token.push(tc);
+                }
+                output.push_str(&resolve_placeholder(&token, record, headers));
+            }
+            c => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// Resolve a single `{...}` placeholder to its field value.
+fn resolve_placeholder(token: &str, record: &StringRecord, headers: Option<&StringRecord>) -> String {
+    if let Ok(index) = token.parse::<usize>() {
+        return record.get(index).unwrap_or("").to_string();
+    }
+
+    if let Some(index) = headers.and_then(|h| h.iter().position(|name| name == token)) {
+        return record.get(index).unwrap_or("").to_string();
+    }
+
+    String::new()
+}
+
+/// Load records into an in-memory SQLite table and run a query over them,
+/// returning the result's column names and rows.
+fn run_query(
+    records: &[StringRecord],
+    header: bool,
+    sql: &str,
+) -> rusqlite::Result<(Vec<String>, Vec<Vec<String>>)> {
+    let (header_row, rows) = if header && !records.is_empty() {
+        (records.first(), &records[1..])
+    } else {
+        (None, records)
+    };
+
+    let columns: Vec<String> = match header_row {
+        Some(names) => names.iter().map(|n| n.to_string()).collect(),
+        None => {
+            let width = rows.first().map_or(0, |r| r.len());
+            (0..width).map(|i| format!("c{}", i)).collect::<Vec<_>>()
+        }
+    };
+
+    // No columns to query (e.g. empty input without --header): nothing to do.
+    if columns.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let conn = Connection::open_in_memory()?;
+    conn.execute(&create_table_sql(&columns), [])?;
+
+    let placeholders = (1..=columns.len())
+        .map(|i| format!("?{}", i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let insert_sql = format!("INSERT INTO data VALUES ({})", placeholders);
+    let mut insert = conn.prepare(&insert_sql)?;
+    for record in rows {
+        insert.execute(rusqlite::params_from_iter(record.iter()))?;
+    }
+
+    let mut stmt = conn.prepare(sql)?;
+    let result_columns: Vec<String> = stmt.column_names().iter().map(|n| n.to_string()).collect();
+    let result_rows = stmt
+        .query_map([], |row| {
+            (0..result_columns.len())
+                .map(|i| row.get_ref(i).map(value_ref_to_string))
+                .collect::<rusqlite::Result<Vec<String>>>()
+        })?
+        .collect::<rusqlite::Result<Vec<Vec<String>>>>()?;
+
+    Ok((result_columns, result_rows))
+}
+
+/// Build the `CREATE TABLE` statement for a `data` table with the given columns.
+fn create_table_sql(columns: &[String]) -> String {
+    let column_defs = columns
+        .iter()
+        .map(|c| format!("\"{}\" TEXT", c.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!("CREATE TABLE data ({})", column_defs)
+}
+
+/// Render a SQLite value as the string rtab displays it.
+fn value_ref_to_string(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
+        ValueRef::Blob(_) => "<blob>".to_string(),
+    }
+}
+
+/// Render columns and rows as tab-separated values.
+fn tsv_table(columns: &[String], rows: &[Vec<String>]) -> String {
+    let mut output = String::new();
+    writeln!(output, "{}", columns.join("\t")).unwrap();
+    for row in rows {
+        writeln!(output, "{}", row.join("\t")).unwrap();
+    }
+
+    output
+}
+
 /// Calculate widths of each record.
 fn calculate_widths(records: &[StringRecord]) -> Vec<usize> {
-    // Find the maximum width per column.
+    // Find the maximum display width per column.
     let length = records.first().map_or(0, |r| r.len());
     records.iter().fold(vec![0; length], |acc, r| {
         acc.iter()
             .zip(r.iter())
-            .map(|e| (*e.0).max(e.1.len()))
+            .map(|e| (*e.0).max(e.1.width()))
             .collect()
     })
 }
 
-/// Read records from file.
-fn parse_records(path: &str) -> csv::Result<Vec<StringRecord>> {
-    ReaderBuilder::new()
-        .has_headers(false)
-        .trim(Trim::All)
-        .from_path(path)?
-        .records()
-        .collect()
+/// Read records from a file, or from stdin when `path` is `-`.
+fn parse_records(path: &str, delimiter: u8) -> csv::Result<Vec<StringRecord>> {
+    let mut builder = ReaderBuilder::new();
+    builder.has_headers(false).trim(Trim::All).delimiter(delimiter);
+
+    if path == "-" {
+        builder.from_reader(io::stdin()).records().collect()
+    } else {
+        builder.from_path(path)?.records().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_field_left_pads_with_trailing_spaces() {
+        assert_eq!(align_field("ab", 5, Alignment::Left), "ab   ");
+    }
+
+    #[test]
+    fn align_field_right_pads_with_leading_spaces() {
+        assert_eq!(align_field("ab", 5, Alignment::Right), "   ab");
+    }
+
+    #[test]
+    fn align_field_center_splits_padding_around_the_field() {
+        assert_eq!(align_field("ab", 6, Alignment::Center), "  ab  ");
+        assert_eq!(align_field("ab", 5, Alignment::Center), " ab  ");
+    }
+
+    #[test]
+    fn render_template_substitutes_by_index_and_escapes_braces() {
+        let record = StringRecord::from(vec!["Alice", "100"]);
+        let rendered = render_template("{{id}} {0}={1}", &record, None);
+        assert_eq!(rendered, "{id} Alice=100");
+    }
+
+    #[test]
+    fn render_template_substitutes_by_header_name() {
+        let headers = StringRecord::from(vec!["Name", "Amount"]);
+        let record = StringRecord::from(vec!["Alice", "100"]);
+        let rendered = render_template("{Name}: {Amount}", &record, Some(&headers));
+        assert_eq!(rendered, "Alice: 100");
+    }
+
+    #[test]
+    fn auto_alignment_ignores_the_header_row() {
+        let records = vec![
+            StringRecord::from(vec!["Name", "Price"]),
+            StringRecord::from(vec!["Apple", "1.50"]),
+            StringRecord::from(vec!["Pear", "2.00"]),
+        ];
+        assert!(matches!(auto_alignment(&records, 1, true), Alignment::Right));
+        assert!(matches!(auto_alignment(&records, 0, true), Alignment::Left));
+    }
+
+    #[test]
+    fn auto_alignment_without_header_considers_every_row() {
+        let records = vec![
+            StringRecord::from(vec!["Price"]),
+            StringRecord::from(vec!["1.50"]),
+        ];
+        assert!(matches!(auto_alignment(&records, 0, false), Alignment::Left));
+    }
 }